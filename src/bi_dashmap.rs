@@ -0,0 +1,358 @@
+//! A concurrent, sharded bidirectional map usable from multiple threads behind `&self`.
+//!
+//! This takes the sharding approach `dashmap` uses: the keyspace is partitioned into a fixed
+//! number of shards, each guarded by its own `RwLock`, so operations on keys routed to different
+//! shards can proceed concurrently instead of contending on one global lock.
+
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+use std::ops::Deref;
+use std::sync::{RwLock, RwLockReadGuard};
+
+struct Shard<K, V, S> {
+    fwd: HashMap<K, V, S>,
+    rev: HashMap<V, K, S>,
+}
+
+/// A concurrent bidirectional map. See the [module][self] docs for the sharding scheme.
+pub struct BiDashmap<K, V, S = RandomState> {
+    shards: Vec<RwLock<Shard<K, V, S>>>,
+    hash_builder: S,
+}
+
+impl<K, V> BiDashmap<K, V, RandomState>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash + Clone,
+{
+    /// Creates an empty `BiDashmap`, sharded across `available_parallelism` (rounded up to a
+    /// power of two) shards.
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::default())
+    }
+}
+
+impl<K, V> Default for BiDashmap<K, V, RandomState>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, S> BiDashmap<K, V, S>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash + Clone,
+    S: BuildHasher + Clone,
+{
+    /// Creates an empty `BiDashmap` with the given hasher, sharded across `available_parallelism`
+    /// (rounded up to a power of two) shards.
+    pub fn with_hasher(hash_builder: S) -> Self {
+        let shard_count = default_shard_count();
+        let shards = (0..shard_count)
+            .map(|_| {
+                RwLock::new(Shard {
+                    fwd: HashMap::with_hasher(hash_builder.clone()),
+                    rev: HashMap::with_hasher(hash_builder.clone()),
+                })
+            })
+            .collect();
+        Self {
+            shards,
+            hash_builder,
+        }
+    }
+
+    fn hash_of<Q: Hash + ?Sized>(&self, value: &Q) -> u64 {
+        self.hash_builder.hash_one(value)
+    }
+
+    /// Maps a hash to a shard index using its top bits, leaving the bits that index within a
+    /// shard's own hash table untouched.
+    fn shard_index(&self, hash: u64) -> usize {
+        let shard_count = self.shards.len();
+        if shard_count == 1 {
+            return 0;
+        }
+        let shift = 64 - shard_count.trailing_zeros();
+        (hash >> shift) as usize
+    }
+
+    /// Inserts a (key, value) pair into the map. Panics if either the key or value is already
+    /// present.
+    ///
+    /// A bijective insert touches the key's shard and the value's shard, which may differ; both
+    /// are locked in ascending shard-index order so that two concurrent inserts can never wait on
+    /// each other in opposite orders. The collision check always runs to completion and drops its
+    /// guard(s) *before* panicking: a `std::sync::RwLock` poisons itself if a guard is dropped
+    /// while the thread is unwinding from a panic, and poisoning a shard here would permanently
+    /// brick it (every other method on this type uses `.unwrap()` on the lock result with no
+    /// poison recovery), taking down unrelated keys along with the duplicate that caused it.
+    pub fn insert(&self, k: K, v: V) {
+        let shard_k = self.shard_index(self.hash_of(&k));
+        let shard_v = self.shard_index(self.hash_of(&v));
+        let (lo, hi) = (shard_k.min(shard_v), shard_k.max(shard_v));
+
+        let mut lo_guard = self.shards[lo].write().unwrap();
+        if lo == hi {
+            if lo_guard.fwd.contains_key(&k) || lo_guard.rev.contains_key(&v) {
+                drop(lo_guard);
+                panic!("Element already in bimap");
+            }
+            lo_guard.fwd.insert(k.clone(), v.clone());
+            lo_guard.rev.insert(v, k);
+        } else {
+            let mut hi_guard = self.shards[hi].write().unwrap();
+            let collision = if shard_k == lo {
+                lo_guard.fwd.contains_key(&k) || hi_guard.rev.contains_key(&v)
+            } else {
+                hi_guard.fwd.contains_key(&k) || lo_guard.rev.contains_key(&v)
+            };
+            if collision {
+                drop(hi_guard);
+                drop(lo_guard);
+                panic!("Element already in bimap");
+            }
+            let (fwd_shard, rev_shard) = if shard_k == lo {
+                (&mut *lo_guard, &mut *hi_guard)
+            } else {
+                (&mut *hi_guard, &mut *lo_guard)
+            };
+            fwd_shard.fwd.insert(k.clone(), v.clone());
+            rev_shard.rev.insert(v, k);
+        }
+    }
+
+    /// Gets the value corresponding to a key, behind a read guard on the key's shard.
+    pub fn get_fwd(&self, k: &K) -> Option<FwdRef<'_, K, V, S>> {
+        let shard_idx = self.shard_index(self.hash_of(k));
+        let guard = self.shards[shard_idx].read().unwrap();
+        if guard.fwd.contains_key(k) {
+            Some(FwdRef {
+                guard,
+                key: k.clone(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Gets the key corresponding to a value, behind a read guard on the value's shard.
+    pub fn get_rev(&self, v: &V) -> Option<RevRef<'_, K, V, S>> {
+        let shard_idx = self.shard_index(self.hash_of(v));
+        let guard = self.shards[shard_idx].read().unwrap();
+        if guard.rev.contains_key(v) {
+            Some(RevRef {
+                guard,
+                value: v.clone(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Removes the (key, value) pair with the given key; returns the corresponding value, if
+    /// present.
+    pub fn remove_fwd(&self, k: &K) -> Option<V> {
+        loop {
+            let shard_k = self.shard_index(self.hash_of(k));
+            let v_guess = {
+                let guard = self.shards[shard_k].read().unwrap();
+                guard.fwd.get(k)?.clone()
+            };
+            let shard_v = self.shard_index(self.hash_of(&v_guess));
+
+            if shard_k == shard_v {
+                let mut shard = self.shards[shard_k].write().unwrap();
+                // Re-validate under the lock: the peek above may be stale, and the value now
+                // sitting at `k` (if any) might hash to a different shard than `v_guess` did, in
+                // which case its `rev` entry isn't in `shard` at all. Only commit to the
+                // single-shard removal if the current value still routes back to this shard;
+                // otherwise retry so the pair gets routed (and locked) correctly.
+                match shard.fwd.get(k) {
+                    Some(v) if self.shard_index(self.hash_of(v)) == shard_k => {}
+                    _ => {
+                        drop(shard);
+                        continue;
+                    }
+                }
+                let v = shard.fwd.remove(k).unwrap();
+                shard.rev.remove(&v);
+                return Some(v);
+            }
+
+            let (lo, hi) = (shard_k.min(shard_v), shard_k.max(shard_v));
+            let mut lo_guard = self.shards[lo].write().unwrap();
+            let mut hi_guard = self.shards[hi].write().unwrap();
+            let (fwd_shard, rev_shard) = if shard_k == lo {
+                (&mut *lo_guard, &mut *hi_guard)
+            } else {
+                (&mut *hi_guard, &mut *lo_guard)
+            };
+
+            match fwd_shard.fwd.get(k) {
+                Some(v) if *v == v_guess => {}
+                _ => continue,
+            }
+            let v = fwd_shard.fwd.remove(k).unwrap();
+            rev_shard.rev.remove(&v);
+            return Some(v);
+        }
+    }
+
+    /// Removes the (key, value) pair with the given value; returns the corresponding key, if
+    /// present.
+    pub fn remove_rev(&self, v: &V) -> Option<K> {
+        loop {
+            let shard_v = self.shard_index(self.hash_of(v));
+            let k_guess = {
+                let guard = self.shards[shard_v].read().unwrap();
+                guard.rev.get(v)?.clone()
+            };
+            let shard_k = self.shard_index(self.hash_of(&k_guess));
+
+            if shard_k == shard_v {
+                let mut shard = self.shards[shard_v].write().unwrap();
+                // See the matching comment in `remove_fwd`: re-validate that the key currently
+                // paired with `v` still routes to this shard before removing from both maps.
+                match shard.rev.get(v) {
+                    Some(k) if self.shard_index(self.hash_of(k)) == shard_v => {}
+                    _ => {
+                        drop(shard);
+                        continue;
+                    }
+                }
+                let k = shard.rev.remove(v).unwrap();
+                shard.fwd.remove(&k);
+                return Some(k);
+            }
+
+            let (lo, hi) = (shard_k.min(shard_v), shard_k.max(shard_v));
+            let mut lo_guard = self.shards[lo].write().unwrap();
+            let mut hi_guard = self.shards[hi].write().unwrap();
+            let (fwd_shard, rev_shard) = if shard_k == lo {
+                (&mut *lo_guard, &mut *hi_guard)
+            } else {
+                (&mut *hi_guard, &mut *lo_guard)
+            };
+
+            match rev_shard.rev.get(v) {
+                Some(k) if *k == k_guess => {}
+                _ => continue,
+            }
+            let k = rev_shard.rev.remove(v).unwrap();
+            fwd_shard.fwd.remove(&k);
+            return Some(k);
+        }
+    }
+
+    /// Returns whether the map contains a (key, value) pair with the given key.
+    pub fn contains_fwd(&self, k: &K) -> bool {
+        let shard_idx = self.shard_index(self.hash_of(k));
+        self.shards[shard_idx].read().unwrap().fwd.contains_key(k)
+    }
+
+    /// Returns whether the map contains a (key, value) pair with the given value.
+    pub fn contains_rev(&self, v: &V) -> bool {
+        let shard_idx = self.shard_index(self.hash_of(v));
+        self.shards[shard_idx].read().unwrap().rev.contains_key(v)
+    }
+}
+
+/// An RAII read guard giving access to the value corresponding to a key, returned by
+/// [`BiDashmap::get_fwd`]. The key's shard remains read-locked for as long as this is alive.
+pub struct FwdRef<'a, K, V, S> {
+    guard: RwLockReadGuard<'a, Shard<K, V, S>>,
+    key: K,
+}
+
+impl<K: Eq + Hash, V, S: BuildHasher> Deref for FwdRef<'_, K, V, S> {
+    type Target = V;
+
+    fn deref(&self) -> &V {
+        self.guard
+            .fwd
+            .get(&self.key)
+            .expect("entry removed while FwdRef was held")
+    }
+}
+
+/// An RAII read guard giving access to the key corresponding to a value, returned by
+/// [`BiDashmap::get_rev`]. The value's shard remains read-locked for as long as this is alive.
+pub struct RevRef<'a, K, V, S> {
+    guard: RwLockReadGuard<'a, Shard<K, V, S>>,
+    value: V,
+}
+
+impl<K, V: Eq + Hash, S: BuildHasher> Deref for RevRef<'_, K, V, S> {
+    type Target = K;
+
+    fn deref(&self) -> &K {
+        self.guard
+            .rev
+            .get(&self.value)
+            .expect("entry removed while RevRef was held")
+    }
+}
+
+/// The default shard count: `available_parallelism`, rounded up to a power of two so that
+/// [`BiDashmap::shard_index`] can select a shard with a plain bit-shift.
+fn default_shard_count() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .next_power_of_two()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn concurrent_insert_and_remove_never_leaks_a_rev_entry() {
+        // Each thread inserts its own (key, value) pairs, racing with every other thread's
+        // inserts and removes on neighboring shards, then removes what it just inserted. All
+        // keys and values are distinct and each key is only touched by its own thread, so no
+        // insert collides and no pair changes between a remove's peek and its lock; what this
+        // checks is that concurrent same-shard and cross-shard insert/remove never panics and
+        // never leaves an orphaned `rev` entry behind (detected below).
+        let map: Arc<BiDashmap<u64, u64>> = Arc::new(BiDashmap::new());
+        const THREADS: u64 = 8;
+        const ITERATIONS: u64 = 500;
+
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let map = Arc::clone(&map);
+                thread::spawn(move || {
+                    let mut values = Vec::with_capacity(ITERATIONS as usize);
+                    for i in 0..ITERATIONS {
+                        let k = t * ITERATIONS + i;
+                        // Scramble the value so it's unlikely to land in the same shard as its
+                        // key, exercising the cross-shard locking path as well as the same-shard
+                        // one.
+                        let v = k.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ 0xDEAD_BEEF;
+                        map.insert(k, v);
+                        assert_eq!(map.remove_fwd(&k), Some(v));
+                        values.push(v);
+                    }
+                    values
+                })
+            })
+            .collect();
+
+        let mut all_values = Vec::new();
+        for handle in handles {
+            all_values.extend(handle.join().unwrap());
+        }
+
+        for v in all_values {
+            assert!(!map.contains_rev(&v), "orphaned rev entry for {v}");
+        }
+    }
+}
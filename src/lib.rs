@@ -1,27 +1,130 @@
-//! A two-way map data structure for cloneable keys and values.
+//! A two-way map data structure.
 //!
 //! Most functions come in `_fwd` and `_rev` variants; where the `_fwd` variant acts on the second
 //! entry given the first, and `_rev` is the opposite.
 //!
-//! This crate is best for values that are cheap to clone, since internally it stores two copies
-//! of each element. To use it with large values, consider wrapping them in `Rc` to make them cheap
-//! to clone.
+//! Internally, each (key, value) pair is stored exactly once, in a reference-counted allocation
+//! shared between the forward and reverse indices. This means cloning a `Bimap` is cheap (it only
+//! bumps reference counts) and inserting no longer requires cloning both the key and the value.
+//!
+//! # Breaking change in 0.2: lookups take `&K`/`&V` exactly
+//!
+//! Before this redesign, `get_fwd` and friends accepted any `Q` with `K: Borrow<Q>` (e.g.
+//! `get_fwd("a")` on a `Bimap<String, V>`). That relied on `HashMap`'s own blanket `Borrow<Q>`
+//! support for its literal key type; the forward/reverse indices here are now keyed by `KeyRef`/
+//! `ValRef` wrappers around the shared `Rc`, which would need their own generic `Borrow<Q>` impl
+//! to offer the same thing. That impl is not expressible on stable Rust: `impl<K, V, Q: ?Sized>
+//! Borrow<Q> for KeyRef<K, V>` conflicts with the standard library's reflexive `impl<T> Borrow<T>
+//! for T` (`Q` can unify with `KeyRef<K, V>` itself, and coherence checking doesn't consider the
+//! `K: Borrow<Q>` bound when deciding that). Picking the Rc-sharing design cost this API, so every
+//! lookup/removal method below takes the exact key or value type instead.
 
 use std::borrow::Borrow;
-use std::collections::hash_map::*;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
 use std::default::Default;
-use std::hash::{BuildHasher, Hash};
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::rc::Rc;
+
+#[cfg(feature = "serde")]
+mod serde_impl;
+
+mod bi_dashmap;
+pub use bi_dashmap::{BiDashmap, FwdRef, RevRef};
+
+mod with_value;
+pub use with_value::BimapWithValue;
+
+/// A (key, value) pair shared between the forward and reverse indices, so that it's stored
+/// exactly once no matter how many internal maps reference it.
+type Pair<K, V> = Rc<(K, V)>;
+
+/// A `HashMap` key that hashes and compares by a pair's key half, while still giving access to
+/// the whole pair through the shared `Rc`.
+struct KeyRef<K, V>(Pair<K, V>);
+
+/// A `HashMap` key that hashes and compares by a pair's value half, while still giving access to
+/// the whole pair through the shared `Rc`.
+struct ValRef<K, V>(Pair<K, V>);
+
+impl<K: Hash, V> Hash for KeyRef<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0 .0.hash(state)
+    }
+}
+impl<K: PartialEq, V> PartialEq for KeyRef<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 .0 == other.0 .0
+    }
+}
+impl<K: Eq, V> Eq for KeyRef<K, V> {}
+impl<K, V> Clone for KeyRef<K, V> {
+    fn clone(&self) -> Self {
+        KeyRef(self.0.clone())
+    }
+}
+impl<K, V> Borrow<K> for KeyRef<K, V> {
+    fn borrow(&self) -> &K {
+        &self.0 .0
+    }
+}
+
+impl<K, V: Hash> Hash for ValRef<K, V> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0 .1.hash(state)
+    }
+}
+impl<K, V: PartialEq> PartialEq for ValRef<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 .1 == other.0 .1
+    }
+}
+impl<K, V: Eq> Eq for ValRef<K, V> {}
+impl<K, V> Clone for ValRef<K, V> {
+    fn clone(&self) -> Self {
+        ValRef(self.0.clone())
+    }
+}
+impl<K, V> Borrow<V> for ValRef<K, V> {
+    fn borrow(&self) -> &V {
+        &self.0 .1
+    }
+}
 
-#[derive(Clone)]
 pub struct Bimap<K, V, S = RandomState> {
-    fwd: HashMap<K, V, S>,
-    rev: HashMap<V, K, S>,
+    fwd: HashMap<KeyRef<K, V>, Pair<K, V>, S>,
+    rev: HashMap<ValRef<K, V>, Pair<K, V>, S>,
+}
+
+impl<K, V, S: Clone> Clone for Bimap<K, V, S> {
+    fn clone(&self) -> Self {
+        Self {
+            fwd: self.fwd.clone(),
+            rev: self.rev.clone(),
+        }
+    }
+}
+
+/// Describes which existing pairs, if any, were evicted by a call to
+/// [`Bimap::insert_overwrite`], or would have been evicted by a call to [`Bimap::try_insert`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Overwritten<K, V> {
+    /// Neither the key nor the value was already present in the bimap.
+    Neither,
+    /// The key was already present, mapped to a different value; that pair was evicted.
+    Fwd(K, V),
+    /// The value was already present, mapped to a different key; that pair was evicted.
+    Rev(K, V),
+    /// The exact (key, value) pair was already present.
+    Pair(K, V),
+    /// The key and value each belonged to a different existing pair; both were evicted.
+    Both((K, V), (K, V)),
 }
 
 impl<K, V> Bimap<K, V, RandomState>
 where
-    K: Eq + Hash + Clone,
-    V: Eq + Hash + Clone,
+    K: Eq + Hash,
+    V: Eq + Hash,
 {
     /// Creates an empty `Bimap`.
     pub fn new() -> Self {
@@ -30,12 +133,20 @@ where
             rev: HashMap::new(),
         }
     }
+
+    /// Creates an empty `Bimap` with at least the specified capacity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            fwd: HashMap::with_capacity(capacity),
+            rev: HashMap::with_capacity(capacity),
+        }
+    }
 }
 
 impl<K, V, S> Bimap<K, V, S>
 where
-    K: Eq + Hash + Clone,
-    V: Eq + Hash + Clone,
+    K: Eq + Hash,
+    V: Eq + Hash,
     S: BuildHasher + Clone + Default,
 {
     /// Creates a `Bimap` with the given hasher.
@@ -46,10 +157,21 @@ where
         }
     }
 
+    /// Creates an empty `Bimap` with at least the specified capacity, using the given hasher.
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        Self {
+            fwd: HashMap::with_capacity_and_hasher(capacity, hash_builder.clone()),
+            rev: HashMap::with_capacity_and_hasher(capacity, hash_builder),
+        }
+    }
+
     /// Creates a bimap from a `HashMap`.
     pub fn from_hash_map(fwd: HashMap<K, V, S>) -> Self {
-        let rev = fwd.iter().map(|(k, v)| (v.clone(), k.clone())).collect();
-        Self { fwd, rev }
+        let mut bimap = Self::with_capacity_and_hasher(fwd.len(), S::default());
+        for (k, v) in fwd {
+            bimap.insert(k, v);
+        }
+        bimap
     }
 
     /// Returns the number of elements in the bimap.
@@ -68,95 +190,473 @@ where
         self.rev.clear();
     }
 
+    /// Returns a lower bound on the number of elements the bimap can hold without reallocating,
+    /// i.e. the capacity of whichever of the two inner maps has less room left.
+    pub fn capacity(&self) -> usize {
+        self.fwd.capacity().min(self.rev.capacity())
+    }
+
+    /// Reserves capacity for at least `additional` more elements to be inserted in the bimap.
+    pub fn reserve(&mut self, additional: usize) {
+        self.fwd.reserve(additional);
+        self.rev.reserve(additional);
+    }
+
+    /// Shrinks the capacity of the bimap as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.fwd.shrink_to_fit();
+        self.rev.shrink_to_fit();
+    }
+
     /// Inserts a (key, value) pair into the bimap. Panics if either the key or value is already
     /// present in the bimap; to change a key or value, call either `remove_fwd` or
     /// `remove_rev` before inserting the new (key, value) pair.
     pub fn insert(&mut self, k: K, v: V) {
-        match self.fwd.entry(k.clone()) {
-            Entry::Vacant(entry) => {
-                entry.insert(v.clone());
-            }
-            Entry::Occupied(_) => panic!("Element aready in bimap"),
+        if self.fwd.contains_key(&k) || self.rev.contains_key(&v) {
+            panic!("Element aready in bimap");
         }
-        match self.rev.entry(v) {
-            Entry::Vacant(entry) => {
-                entry.insert(k);
+        let pair = Rc::new((k, v));
+        self.fwd.insert(KeyRef(pair.clone()), pair.clone());
+        self.rev.insert(ValRef(pair.clone()), pair);
+    }
+
+    /// Inserts a (key, value) pair into the bimap, evicting any existing pair(s) that collide
+    /// with either the key or the value, so that the bijection invariant is preserved. Returns
+    /// an [`Overwritten`] describing what (if anything) was evicted.
+    pub fn insert_overwrite(&mut self, k: K, v: V) -> Overwritten<K, V> {
+        let old_fwd = self.fwd.remove(&k);
+        let old_rev = self.rev.remove(&v);
+
+        let overwritten = match (old_fwd, old_rev) {
+            (None, None) => Overwritten::Neither,
+            (Some(old_pair), None) => {
+                self.rev.remove(&ValRef(old_pair.clone()));
+                let (old_k, old_v) = unwrap_pair(old_pair);
+                Overwritten::Fwd(old_k, old_v)
             }
-            Entry::Occupied(_) => panic!("Element aready in bimap"),
-        }
+            (None, Some(old_pair)) => {
+                self.fwd.remove(&KeyRef(old_pair.clone()));
+                let (old_k, old_v) = unwrap_pair(old_pair);
+                Overwritten::Rev(old_k, old_v)
+            }
+            (Some(fwd_pair), Some(rev_pair)) => {
+                if Rc::ptr_eq(&fwd_pair, &rev_pair) {
+                    // `fwd_pair` and `rev_pair` are two `Rc` clones of the same allocation; drop
+                    // one before unwrapping the other so `unwrap_pair` sees a unique owner.
+                    drop(rev_pair);
+                    let (old_k, old_v) = unwrap_pair(fwd_pair);
+                    Overwritten::Pair(old_k, old_v)
+                } else {
+                    self.rev.remove(&ValRef(fwd_pair.clone()));
+                    self.fwd.remove(&KeyRef(rev_pair.clone()));
+                    let (k1, v1) = unwrap_pair(fwd_pair);
+                    let (k2, v2) = unwrap_pair(rev_pair);
+                    Overwritten::Both((k1, v1), (k2, v2))
+                }
+            }
+        };
+
+        let pair = Rc::new((k, v));
+        self.fwd.insert(KeyRef(pair.clone()), pair.clone());
+        self.rev.insert(ValRef(pair.clone()), pair);
+
+        overwritten
     }
 
-    /// Gets the value corresponding to a key.
-    pub fn get_fwd<KeyBorrow: ?Sized>(&self, k: &KeyBorrow) -> Option<&V>
+    /// Attempts to insert a (key, value) pair, leaving the bimap unchanged if the key or value
+    /// (or both) already exist. On failure, returns an [`Overwritten`] describing the collision
+    /// that would have occurred (the `Neither` variant is never returned as an error).
+    ///
+    /// Unlike [`Bimap::insert_overwrite`], this needs to clone the colliding pair(s) to report
+    /// them without removing them, so it requires `K` and `V` to be `Clone`.
+    pub fn try_insert(&mut self, k: K, v: V) -> Result<(), Overwritten<K, V>>
     where
-        K: Borrow<KeyBorrow>,
-        KeyBorrow: Hash + Eq,
+        K: Clone,
+        V: Clone,
     {
-        self.fwd.get(k)
+        let old_fwd = self.fwd.get(&k).cloned();
+        let old_rev = self.rev.get(&v).cloned();
+
+        match (old_fwd, old_rev) {
+            (None, None) => {
+                let pair = Rc::new((k, v));
+                self.fwd.insert(KeyRef(pair.clone()), pair.clone());
+                self.rev.insert(ValRef(pair.clone()), pair);
+                Ok(())
+            }
+            (Some(fwd_pair), None) => Err(Overwritten::Fwd(fwd_pair.0.clone(), fwd_pair.1.clone())),
+            (None, Some(rev_pair)) => Err(Overwritten::Rev(rev_pair.0.clone(), rev_pair.1.clone())),
+            (Some(fwd_pair), Some(rev_pair)) => {
+                if Rc::ptr_eq(&fwd_pair, &rev_pair) {
+                    Err(Overwritten::Pair(fwd_pair.0.clone(), fwd_pair.1.clone()))
+                } else {
+                    Err(Overwritten::Both(
+                        (fwd_pair.0.clone(), fwd_pair.1.clone()),
+                        (rev_pair.0.clone(), rev_pair.1.clone()),
+                    ))
+                }
+            }
+        }
     }
 
-    /// Gets the key corresponding to a value.
-    pub fn get_rev<ValBorrow: ?Sized>(&self, v: &ValBorrow) -> Option<&K>
-    where
-        V: Borrow<ValBorrow>,
-        ValBorrow: Hash + Eq,
-    {
-        self.rev.get(v)
+    /// Gets the value corresponding to a key. Takes `&K` exactly; see the crate docs' note on the
+    /// 0.2 breaking change for why this no longer accepts a borrowed form like `&str` for
+    /// `K = String`.
+    pub fn get_fwd(&self, k: &K) -> Option<&V> {
+        self.fwd.get(k).map(|pair| &pair.1)
     }
 
-    /// Removes the (key, value) pair with the given key; returns the corresponding value.
-    pub fn remove_fwd<KeyBorrow: ?Sized>(&mut self, k: &KeyBorrow) -> V
-    where
-        K: Borrow<KeyBorrow>,
-        KeyBorrow: Hash + Eq,
-    {
-        let v = self.fwd.remove(k).unwrap();
-        self.rev.remove(&v);
-        v
+    /// Gets the key corresponding to a value. Takes `&V` exactly; see [`Bimap::get_fwd`].
+    pub fn get_rev(&self, v: &V) -> Option<&K> {
+        self.rev.get(v).map(|pair| &pair.0)
     }
 
-    /// Removes the (key, value) pair with the given value; returns the corresponding key.
-    pub fn remove_rev<ValBorrow: ?Sized>(&mut self, v: &ValBorrow) -> K
-    where
-        V: Borrow<ValBorrow>,
-        ValBorrow: Hash + Eq,
-    {
-        let k = self.rev.remove(v).unwrap();
-        self.fwd.remove(&k);
-        k
+    /// Removes the (key, value) pair with the given key; returns the corresponding value. Takes
+    /// `&K` exactly; see [`Bimap::get_fwd`].
+    pub fn remove_fwd(&mut self, k: &K) -> V {
+        let pair = self.fwd.remove(k).expect("no such key in bimap");
+        self.rev.remove(&ValRef(pair.clone()));
+        unwrap_pair(pair).1
     }
 
-    /// Returns whether the bimap contains a (key, value) pair with the given key.
-    pub fn contains_fwd<KeyBorrow: ?Sized>(&self, k: &KeyBorrow) -> bool
+    /// Removes the (key, value) pair with the given value; returns the corresponding key. Takes
+    /// `&V` exactly; see [`Bimap::get_fwd`].
+    pub fn remove_rev(&mut self, v: &V) -> K {
+        let pair = self.rev.remove(v).expect("no such value in bimap");
+        self.fwd.remove(&KeyRef(pair.clone()));
+        unwrap_pair(pair).0
+    }
+
+    /// Removes the (key, value) pair with the given key, returning the whole pair, or `None` if
+    /// no such key is present. Unlike `remove_fwd`, this never panics and hands back the owned
+    /// key that was actually stored, not just the caller's borrowed form of it. Takes `&K`
+    /// exactly; see [`Bimap::get_fwd`].
+    pub fn take_fwd(&mut self, k: &K) -> Option<(K, V)> {
+        let pair = self.fwd.remove(k)?;
+        self.rev.remove(&ValRef(pair.clone()));
+        Some(unwrap_pair(pair))
+    }
+
+    /// Removes the (key, value) pair with the given value, returning the whole pair, or `None` if
+    /// no such value is present. Unlike `remove_rev`, this never panics and hands back the owned
+    /// value that was actually stored, not just the caller's borrowed form of it. Takes `&V`
+    /// exactly; see [`Bimap::get_fwd`].
+    pub fn take_rev(&mut self, v: &V) -> Option<(K, V)> {
+        let pair = self.rev.remove(v)?;
+        self.fwd.remove(&KeyRef(pair.clone()));
+        Some(unwrap_pair(pair))
+    }
+
+    /// Returns whether the bimap contains a (key, value) pair with the given key. Takes `&K`
+    /// exactly; see [`Bimap::get_fwd`].
+    pub fn contains_fwd(&self, k: &K) -> bool {
+        self.fwd.contains_key(k)
+    }
+
+    /// Returns whether the bimap contains a (key, value) pair with the given value. Takes `&V`
+    /// exactly; see [`Bimap::get_fwd`].
+    pub fn contains_rev(&self, v: &V) -> bool {
+        self.rev.contains_key(v)
+    }
+
+    /// Iterates over all (key, value) pairs in the bimap.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.fwd.values(),
+        }
+    }
+
+    /// Iterates over all keys in the bimap.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            inner: self.fwd.values(),
+        }
+    }
+
+    /// Iterates over all values in the bimap.
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            inner: self.fwd.values(),
+        }
+    }
+
+    /// Removes all (key, value) pairs from the bimap, returning them as an iterator. If the
+    /// iterator is dropped before being fully consumed, the remaining pairs are dropped too.
+    pub fn drain(&mut self) -> Drain<K, V>
     where
-        K: Borrow<KeyBorrow>,
-        KeyBorrow: Hash + Eq,
+        S: Default,
     {
-        self.fwd.contains_key(k)
+        drop(std::mem::take(&mut self.rev));
+        Drain {
+            inner: std::mem::take(&mut self.fwd).into_values(),
+        }
     }
 
-    /// Returns whether the bimap contains a (key, value) pair with the given value.
-    pub fn contains_rev<ValBorrow: ?Sized>(&self, v: &ValBorrow) -> bool
+    /// Removes all (key, value) pairs for which `f` returns `false`, from both directions.
+    pub fn retain<F>(&mut self, mut f: F)
     where
-        V: Borrow<ValBorrow>,
-        ValBorrow: Hash + Eq,
+        F: FnMut(&K, &V) -> bool,
     {
-        self.rev.contains_key(v)
+        let to_remove: Vec<Pair<K, V>> = self
+            .fwd
+            .values()
+            .filter(|pair| !f(&pair.0, &pair.1))
+            .cloned()
+            .collect();
+        for pair in to_remove {
+            self.fwd.remove(&KeyRef(pair.clone()));
+            self.rev.remove(&ValRef(pair));
+        }
     }
+}
 
-    /// Iterates over all (key, value) pairs in the bimap.
-    pub fn iter(&self) -> Iter<K, V> {
-        self.fwd.iter()
+impl<K, V, S> IntoIterator for Bimap<K, V, S>
+where
+    K: Eq + Hash,
+    V: Eq + Hash,
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        drop(self.rev);
+        IntoIter {
+            inner: self.fwd.into_values(),
+        }
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for Bimap<K, V, RandomState>
+where
+    K: Eq + Hash,
+    V: Eq + Hash,
+{
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut bimap = Bimap::new();
+        bimap.extend(iter);
+        bimap
+    }
+}
+
+impl<K, V, S> Extend<(K, V)> for Bimap<K, V, S>
+where
+    K: Eq + Hash,
+    V: Eq + Hash,
+    S: BuildHasher + Clone + Default,
+{
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (k, v) in iter {
+            self.insert_overwrite(k, v);
+        }
+    }
+}
+
+/// Consumes an `Rc` pair that is known to have no other owners, returning the (key, value) pair
+/// without cloning either half.
+fn unwrap_pair<K, V>(pair: Pair<K, V>) -> (K, V) {
+    Rc::try_unwrap(pair)
+        .unwrap_or_else(|_| unreachable!("bimap pair had unexpected outstanding references"))
+}
+
+/// An iterator over the (key, value) pairs of a [`Bimap`], created by [`Bimap::iter`].
+pub struct Iter<'a, K, V> {
+    inner: std::collections::hash_map::Values<'a, KeyRef<K, V>, Pair<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|pair| (&pair.0, &pair.1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// An iterator over the keys of a [`Bimap`], created by [`Bimap::keys`].
+pub struct Keys<'a, K, V> {
+    inner: std::collections::hash_map::Values<'a, KeyRef<K, V>, Pair<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|pair| &pair.0)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// An iterator over the values of a [`Bimap`], created by [`Bimap::values`].
+pub struct Values<'a, K, V> {
+    inner: std::collections::hash_map::Values<'a, KeyRef<K, V>, Pair<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|pair| &pair.1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// An owning iterator over the (key, value) pairs of a [`Bimap`], created by its `IntoIterator`
+/// implementation.
+pub struct IntoIter<K, V> {
+    inner: std::collections::hash_map::IntoValues<KeyRef<K, V>, Pair<K, V>>,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(unwrap_pair)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// An iterator that drains the (key, value) pairs of a [`Bimap`], created by [`Bimap::drain`].
+pub struct Drain<K, V> {
+    inner: std::collections::hash_map::IntoValues<KeyRef<K, V>, Pair<K, V>>,
+}
+
+impl<K, V> Iterator for Drain<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(unwrap_pair)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
     }
 }
 
 impl<K, V, S> Default for Bimap<K, V, S>
 where
-    K: Eq + Hash + Clone,
-    V: Eq + Hash + Clone,
+    K: Eq + Hash,
+    V: Eq + Hash,
     S: BuildHasher + Clone + Default,
 {
     fn default() -> Self {
         Bimap::with_hasher(Default::default())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every one of these except the `retain` test exercises a path that ends in `unwrap_pair`'s
+    // `Rc::try_unwrap`, which
+    // assumes there is never more than one outstanding `Rc` for a pair at the point it's removed
+    // from both `fwd` and `rev`. If an insert/overwrite/remove path ever left a stray clone of the
+    // `Rc` alive (e.g. a future refactor returning a reference that outlives the removal), these
+    // would panic instead of silently succeeding.
+
+    #[test]
+    fn remove_fwd_and_rev_unwrap_pair_cleanly() {
+        let mut bimap = Bimap::new();
+        bimap.insert(1, "a");
+        bimap.insert(2, "b");
+
+        assert_eq!(bimap.remove_fwd(&1), "a");
+        assert_eq!(bimap.remove_rev(&"b"), 2);
+        assert!(bimap.is_empty());
+    }
+
+    #[test]
+    fn take_fwd_and_rev_unwrap_pair_cleanly() {
+        let mut bimap = Bimap::new();
+        bimap.insert(1, "a");
+        bimap.insert(2, "b");
+
+        assert_eq!(bimap.take_fwd(&1), Some((1, "a")));
+        assert_eq!(bimap.take_rev(&"b"), Some((2, "b")));
+        assert_eq!(bimap.take_fwd(&1), None);
+    }
+
+    #[test]
+    fn insert_overwrite_unwraps_every_eviction_shape() {
+        let mut bimap = Bimap::new();
+
+        assert_eq!(bimap.insert_overwrite(1, "a"), Overwritten::Neither);
+        // Same key, new value: evicts the old pair via the `Fwd` branch.
+        assert_eq!(bimap.insert_overwrite(1, "b"), Overwritten::Fwd(1, "a"));
+        // Same value, new key: evicts via the `Rev` branch.
+        assert_eq!(bimap.insert_overwrite(2, "b"), Overwritten::Rev(1, "b"));
+        // Re-inserting the exact same pair: evicts via the `Pair` branch (fwd and rev entries
+        // are the same `Rc`, exercised via `Rc::ptr_eq`).
+        assert_eq!(bimap.insert_overwrite(2, "b"), Overwritten::Pair(2, "b"));
+
+        bimap.insert(3, "c");
+        // Key and value each belong to a different existing pair: evicts both via `Both`.
+        assert_eq!(
+            bimap.insert_overwrite(2, "c"),
+            Overwritten::Both((2, "b"), (3, "c"))
+        );
+        assert_eq!(bimap.get_fwd(&2), Some(&"c"));
+        assert_eq!(bimap.len(), 1);
+    }
+
+    #[test]
+    fn duplicate_insert_then_use_does_not_corrupt_the_map() {
+        // Adversarial case: attempt a colliding insert that's rejected without mutating the map,
+        // then keep using the map normally afterwards.
+        let mut bimap = Bimap::new();
+        bimap.insert(1, "a");
+
+        assert_eq!(bimap.try_insert(1, "b"), Err(Overwritten::Fwd(1, "a")));
+        assert_eq!(bimap.try_insert(2, "a"), Err(Overwritten::Rev(1, "a")));
+
+        // The rejected inserts must have left the original pair completely untouched.
+        assert_eq!(bimap.get_fwd(&1), Some(&"a"));
+        assert_eq!(bimap.len(), 1);
+
+        assert_eq!(bimap.remove_fwd(&1), "a");
+        assert!(bimap.is_empty());
+    }
+
+    #[test]
+    fn drain_and_into_iter_unwrap_every_pair() {
+        let mut bimap = Bimap::new();
+        bimap.insert(1, "a");
+        bimap.insert(2, "b");
+
+        let mut drained: Vec<_> = bimap.drain().collect();
+        drained.sort();
+        assert_eq!(drained, vec![(1, "a"), (2, "b")]);
+        assert!(bimap.is_empty());
+
+        bimap.insert(3, "c");
+        let mut collected: Vec<_> = bimap.into_iter().collect();
+        collected.sort();
+        assert_eq!(collected, vec![(3, "c")]);
+    }
+
+    #[test]
+    fn retain_removes_rejected_pairs() {
+        let mut bimap = Bimap::new();
+        bimap.insert(1, "a");
+        bimap.insert(2, "b");
+        bimap.insert(3, "c");
+
+        bimap.retain(|k, _| *k != 2);
+
+        assert_eq!(bimap.len(), 2);
+        assert_eq!(bimap.get_fwd(&2), None);
+        assert_eq!(bimap.get_fwd(&1), Some(&"a"));
+    }
+}
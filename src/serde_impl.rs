@@ -0,0 +1,102 @@
+//! `serde` support for [`Bimap`], gated behind the `serde` feature.
+//!
+//! A `Bimap` is serialized as a single sequence of (key, value) pairs (the contents of `fwd`)
+//! rather than as its two internal maps, so the on-disk/wire format doesn't depend on the
+//! implementation detail of storing both directions.
+
+use super::*;
+use serde::de::{Error, SeqAccess, Visitor};
+use serde::ser::SerializeSeq;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+impl<K, V, S> Serialize for Bimap<K, V, S>
+where
+    K: Serialize + Eq + Hash,
+    V: Serialize + Eq + Hash,
+    S: BuildHasher + Clone + Default,
+{
+    fn serialize<Ser: Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for (k, v) in self.iter() {
+            seq.serialize_element(&(k, v))?;
+        }
+        seq.end()
+    }
+}
+
+struct BimapVisitor<K, V, S>(PhantomData<(K, V, S)>);
+
+impl<'de, K, V, S> Visitor<'de> for BimapVisitor<K, V, S>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de> + Eq + Hash,
+    S: BuildHasher + Clone + Default,
+{
+    type Value = Bimap<K, V, S>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a sequence of (key, value) pairs forming a bijection")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bimap = Bimap::with_hasher(S::default());
+        while let Some((k, v)) = seq.next_element::<(K, V)>()? {
+            // Check for a collision with `contains_fwd`/`contains_rev` rather than
+            // `try_insert`, so deserializing doesn't require `K`/`V: Clone` just to report a
+            // duplicate; `Bimap`'s insert and lookup methods don't need that bound.
+            if bimap.contains_fwd(&k) || bimap.contains_rev(&v) {
+                return Err(A::Error::custom(
+                    "duplicate key or value while deserializing a Bimap",
+                ));
+            }
+            bimap.insert(k, v);
+        }
+        Ok(bimap)
+    }
+}
+
+impl<'de, K, V, S> Deserialize<'de> for Bimap<K, V, S>
+where
+    K: Deserialize<'de> + Eq + Hash,
+    V: Deserialize<'de> + Eq + Hash,
+    S: BuildHasher + Clone + Default,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(BimapVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_json() {
+        let mut bimap = Bimap::new();
+        bimap.insert(1, "a".to_string());
+        bimap.insert(2, "b".to_string());
+
+        let json = serde_json::to_string(&bimap).unwrap();
+        let round_tripped: Bimap<i32, String> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(round_tripped.len(), 2);
+        assert_eq!(round_tripped.get_fwd(&1), Some(&"a".to_string()));
+        assert_eq!(round_tripped.get_fwd(&2), Some(&"b".to_string()));
+    }
+
+    #[test]
+    fn duplicate_key_fails_to_deserialize() {
+        let json = r#"[[1, "a"], [1, "b"]]"#;
+        let result: Result<Bimap<i32, String>, _> = serde_json::from_str(json);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn duplicate_value_fails_to_deserialize() {
+        let json = r#"[[1, "a"], [2, "a"]]"#;
+        let result: Result<Bimap<i32, String>, _> = serde_json::from_str(json);
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,160 @@
+//! A [`Bimap`] that also carries a third, non-bijective value alongside each pair.
+
+use super::{Bimap, Overwritten};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash};
+
+/// A bijective `Bimap<K, V>` plus a `HashMap<K, D>` of auxiliary data kept consistent with it.
+///
+/// This is useful when each (key, value) pair needs a third piece of associated data that isn't
+/// itself part of the bijection, e.g. a bimap between IDs and names that also carries metadata
+/// about each entry. Whenever an insert evicts an old pair, the `D` entry for that pair's key is
+/// evicted along with it.
+pub struct BimapWithValue<K, V, D, S = RandomState> {
+    bimap: Bimap<K, V, S>,
+    data: HashMap<K, D, S>,
+}
+
+impl<K, V, D> BimapWithValue<K, V, D, RandomState>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash + Clone,
+{
+    /// Creates an empty `BimapWithValue`.
+    pub fn new() -> Self {
+        Self {
+            bimap: Bimap::new(),
+            data: HashMap::new(),
+        }
+    }
+}
+
+impl<K, V, D> Default for BimapWithValue<K, V, D, RandomState>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, D, S> BimapWithValue<K, V, D, S>
+where
+    K: Eq + Hash + Clone,
+    V: Eq + Hash + Clone,
+    S: BuildHasher + Clone + Default,
+{
+    /// Returns the number of (key, value, data) triples in the map.
+    pub fn len(&self) -> usize {
+        self.bimap.len()
+    }
+
+    /// Returns whether the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.bimap.is_empty()
+    }
+
+    /// Inserts a (key, value, data) triple, evicting any existing pair(s) that collide with
+    /// either the key or the value (along with their associated data), so that the bijection
+    /// invariant of the underlying bimap is preserved. Returns an [`Overwritten`] describing what
+    /// (if anything) was evicted.
+    pub fn insert(&mut self, k: K, v: V, d: D) -> Overwritten<K, V> {
+        let overwritten = self.bimap.insert_overwrite(k.clone(), v);
+        match &overwritten {
+            Overwritten::Neither => {}
+            Overwritten::Fwd(old_k, _) | Overwritten::Rev(old_k, _) | Overwritten::Pair(old_k, _) => {
+                self.data.remove(old_k);
+            }
+            Overwritten::Both((k1, _), (k2, _)) => {
+                self.data.remove(k1);
+                self.data.remove(k2);
+            }
+        }
+        self.data.insert(k, d);
+        overwritten
+    }
+
+    /// Gets the value corresponding to a key.
+    pub fn get_fwd(&self, k: &K) -> Option<&V> {
+        self.bimap.get_fwd(k)
+    }
+
+    /// Gets the key corresponding to a value.
+    pub fn get_rev(&self, v: &V) -> Option<&K> {
+        self.bimap.get_rev(v)
+    }
+
+    /// Gets the data associated with the pair whose key is `k`.
+    pub fn get_data_fwd(&self, k: &K) -> Option<&D> {
+        self.data.get(k)
+    }
+
+    /// Gets the data associated with the pair whose value is `v`, by first resolving `v` to its
+    /// key.
+    pub fn get_data_rev(&self, v: &V) -> Option<&D> {
+        self.bimap.get_rev(v).and_then(|k| self.data.get(k))
+    }
+
+    /// Gets a mutable reference to the data associated with the pair whose key is `k`, for
+    /// in-place updates.
+    pub fn data_mut(&mut self, k: &K) -> Option<&mut D> {
+        self.data.get_mut(k)
+    }
+
+    /// Returns whether the map contains a (key, value) pair with the given key.
+    pub fn contains_fwd(&self, k: &K) -> bool {
+        self.bimap.contains_fwd(k)
+    }
+
+    /// Returns whether the map contains a (key, value) pair with the given value.
+    pub fn contains_rev(&self, v: &V) -> bool {
+        self.bimap.contains_rev(v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_without_collision_keeps_both_entries() {
+        let mut map = BimapWithValue::new();
+        assert_eq!(map.insert(1, "a", "data-a"), Overwritten::Neither);
+
+        assert_eq!(map.get_fwd(&1), Some(&"a"));
+        assert_eq!(map.get_data_fwd(&1), Some(&"data-a"));
+        assert_eq!(map.get_data_rev(&"a"), Some(&"data-a"));
+    }
+
+    #[test]
+    fn insert_evicts_data_for_fwd_and_rev_collisions() {
+        let mut map = BimapWithValue::new();
+        map.insert(1, "a", "data-a");
+
+        // Same key, new value: evicts via `Fwd`, and the old key's data should go with it.
+        assert_eq!(map.insert(1, "b", "data-b"), Overwritten::Fwd(1, "a"));
+        assert_eq!(map.get_data_fwd(&1), Some(&"data-b"));
+
+        // Same value, new key: evicts via `Rev`, and key 1's data goes with the evicted pair.
+        assert_eq!(map.insert(2, "b", "data-2"), Overwritten::Rev(1, "b"));
+        assert_eq!(map.get_data_fwd(&1), None);
+        assert_eq!(map.get_data_fwd(&2), Some(&"data-2"));
+    }
+
+    #[test]
+    fn insert_evicts_data_for_both_cascade() {
+        let mut map = BimapWithValue::new();
+        map.insert(1, "a", "data-1");
+        map.insert(2, "b", "data-2");
+
+        // Key 1 and value "b" each belong to a different existing pair: both are evicted, and
+        // both keys' data entries must be removed along with them.
+        let overwritten = map.insert(1, "b", "data-new");
+        assert_eq!(overwritten, Overwritten::Both((1, "a"), (2, "b")));
+        assert_eq!(map.get_data_fwd(&1), Some(&"data-new"));
+        assert_eq!(map.get_data_fwd(&2), None);
+        assert_eq!(map.len(), 1);
+    }
+}